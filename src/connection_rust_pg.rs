@@ -1,13 +1,21 @@
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
 use postgres::rows::{Row, Rows};
 use postgres::stmt;
-use postgres::types::{self, Type};
+use postgres::types::{self, IsNull, ToSql, Type};
 use postgres::Connection;
-use postgres::TlsMode;
+use postgres::to_sql_checked;
 
 use pyo3::prelude::*;
-use pyo3::types::{PyList, PyTuple};
+use pyo3::types::{PyDict, PyList, PyTuple};
 
-use crate::deserialize_bytes_via_python;
+use crate::{deserialize_bytes_via_python, deserialize_str_via_python};
+use crate::errors::{connection_error, decode_error, query_error};
+use crate::options::{is_transient, ConnectionOptions};
+use crate::params::{extract_params, SlonikParam};
+use crate::tls::{build_tls_mode, TlsOptions};
 
 /// A synchronous PG driver using `rust-postgres`
 #[pyclass]
@@ -18,25 +26,126 @@ pub struct RustPgConnection {
 #[pymethods]
 impl RustPgConnection {
     #[new]
-    fn new(url: &str) -> Self {
-        let connection = Connection::connect(url, TlsMode::None).expect("Couldn't connect");
-        RustPgConnection { connection }
+    fn new(url: &str, options: Option<ConnectionOptions>, tls: Option<TlsOptions>) -> PyResult<Self> {
+        let options = options.unwrap_or_default();
+        let tls = tls.unwrap_or_default();
+        let connection = connect_with_backoff(url, &options, &tls)?;
+        Ok(RustPgConnection { connection })
     }
 
-    fn prepare(&self, query: &'static str) -> Statement {
-        let statement = self.connection.prepare(query).unwrap();
+    fn prepare(&self, query: &'static str) -> PyResult<Statement> {
+        let statement = self.connection.prepare(query).map_err(pg_query_error)?;
         let statement = Box::into_raw(Box::new(statement)) as *mut ();
-        Statement { statement }
+        Ok(Statement { statement })
     }
 
-    fn query<'p>(&self, py: Python<'p>, query: &str) -> PyResult<&'p PyList> {
+    fn query<'p>(&self, py: Python<'p>, query: &str, params: &PyList) -> PyResult<&'p PyList> {
+        let params = extract_params(params)?;
+        let boxed: Vec<Box<dyn ToSql>> = params.iter().map(to_sql_param).collect();
+        let refs: Vec<&dyn ToSql> = boxed.iter().map(AsRef::as_ref).collect();
+
         let rows = self
             .connection
-            .query(query, &[])
-            .expect("Couldn't execute query");
-        let results = deserialize_rows(py, rows);
-        Ok(results)
+            .query(query, &refs)
+            .map_err(pg_query_error)?;
+        deserialize_rows(py, rows)
+    }
+}
+
+// Connects with exponential backoff: starts at `options.initial_delay_ms`,
+// multiplies the delay by `options.backoff_factor` (2.0, i.e. doubling, by
+// default) up to `options.max_interval_ms` between attempts, bounding each individual attempt to
+// `options.connect_timeout_ms`, and only retries on a transient (connection
+// refused/reset/aborted, or timed out) error; anything else is fatal
+// immediately.
+fn connect_with_backoff(
+    url: &str,
+    options: &ConnectionOptions,
+    tls: &TlsOptions,
+) -> PyResult<Connection> {
+    let mut delay = Duration::from_millis(options.initial_delay_ms);
+    let max_interval = Duration::from_millis(options.max_interval_ms);
+    let connect_timeout = Duration::from_millis(options.connect_timeout_ms);
+    let mut last_err = None;
+
+    for attempt in 0..=options.max_retries {
+        match connect_once(url, tls, connect_timeout) {
+            Ok(connection) => return Ok(connection),
+            Err(err) => {
+                if attempt == options.max_retries || !is_transient_connect_outcome(&err) {
+                    return Err(connect_outcome_to_pyerr(err));
+                }
+                last_err = Some(err);
+            }
+        }
+
+        thread::sleep(delay);
+        delay = std::cmp::min(delay.mul_f64(options.backoff_factor), max_interval);
     }
+
+    Err(connect_outcome_to_pyerr(
+        last_err.expect("loop always records an error before exhausting max_retries"),
+    ))
+}
+
+// `Connection::connect` has no built-in timeout, so the attempt is run on
+// its own thread and raced against `timeout` via a channel; a connect that's
+// still hanging once `timeout` elapses is abandoned (the thread is left to
+// finish or fail on its own) rather than blocking the retry loop forever.
+// `tls` is cloned into the thread and turned into a `TlsMode` there instead
+// of here, since the built `TlsMode` itself isn't necessarily `Send`.
+fn connect_once(url: &str, tls: &TlsOptions, timeout: Duration) -> Result<Connection, ConnectOutcome> {
+    let url = url.to_string();
+    let tls = tls.clone();
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let outcome = match build_tls_mode(&tls) {
+            Ok(mode) => Connection::connect(url.as_str(), mode).map_err(ConnectOutcome::Pg),
+            Err(err) => Err(ConnectOutcome::TlsSetup(err)),
+        };
+        let _ = tx.send(outcome);
+    });
+
+    rx.recv_timeout(timeout)
+        .unwrap_or(Err(ConnectOutcome::TimedOut(timeout)))
+}
+
+// The three ways `connect_once` can fail, kept distinct so the backoff loop
+// can decide whether each is worth retrying and so the final error message
+// stays accurate.
+enum ConnectOutcome {
+    Pg(postgres::Error),
+    TlsSetup(PyErr),
+    TimedOut(Duration),
+}
+
+fn is_transient_connect_outcome(err: &ConnectOutcome) -> bool {
+    match err {
+        ConnectOutcome::Pg(err) => is_transient(err),
+        ConnectOutcome::TimedOut(_) => true,
+        ConnectOutcome::TlsSetup(_) => false,
+    }
+}
+
+fn connect_outcome_to_pyerr(err: ConnectOutcome) -> PyErr {
+    match err {
+        ConnectOutcome::Pg(err) => pg_connection_error(err),
+        ConnectOutcome::TlsSetup(err) => err,
+        ConnectOutcome::TimedOut(timeout) => {
+            connection_error(format!("connection attempt timed out after {:?}", timeout), None)
+        }
+    }
+}
+
+fn pg_connection_error(err: postgres::Error) -> PyErr {
+    let sqlstate = err.code().map(|code| code.code());
+    connection_error(err.to_string(), sqlstate)
+}
+
+fn pg_query_error(err: postgres::Error) -> PyErr {
+    let sqlstate = err.code().map(|code| code.code());
+    query_error(err.to_string(), sqlstate)
 }
 
 #[pyclass]
@@ -54,34 +163,69 @@ impl Drop for Statement {
 
 #[pymethods]
 impl Statement {
-    fn query<'p>(&self, py: Python<'p>) -> PyResult<&'p PyList> {
+    fn query<'p>(&self, py: Python<'p>, params: &PyList) -> PyResult<&'p PyList> {
+        let params = extract_params(params)?;
+        let boxed: Vec<Box<dyn ToSql>> = params.iter().map(to_sql_param).collect();
+        let refs: Vec<&dyn ToSql> = boxed.iter().map(AsRef::as_ref).collect();
+
         let statement = self.statement as *const stmt::Statement;
         let statement = unsafe { statement.as_ref().unwrap() };
-        let rows = statement
-            .query(&[])
-            .expect("Couldn't execute prepared statement");
-        let results = deserialize_rows(py, rows);
-        Ok(results)
+        let rows = statement.query(&refs).map_err(pg_query_error)?;
+        deserialize_rows(py, rows)
     }
 }
 
-fn deserialize_rows<'p>(py: Python<'p>, rows: Rows) -> &'p PyList {
+/// A parameter that always serializes as SQL `NULL`, regardless of the
+/// column's actual type.
+struct SqlNull;
+
+impl ToSql for SqlNull {
+    fn to_sql(&self, _ty: &Type, _out: &mut Vec<u8>) -> postgres::Result<IsNull> {
+        Ok(IsNull::Yes)
+    }
+
+    fn accepts(_ty: &Type) -> bool {
+        true
+    }
+
+    to_sql_checked!();
+}
+
+// Turns an already-extracted parameter into the boxed `ToSql` rust-postgres
+// expects for `Connection::query`/`Statement::query`.
+fn to_sql_param(param: &SlonikParam) -> Box<dyn ToSql> {
+    match param {
+        SlonikParam::Null => Box::new(SqlNull),
+        SlonikParam::Bool(value) => Box::new(*value),
+        SlonikParam::I64(value) => Box::new(*value),
+        SlonikParam::F64(value) => Box::new(*value),
+        SlonikParam::Str(value) => Box::new(value.clone()),
+        SlonikParam::Bytes(value) => Box::new(value.clone()),
+    }
+}
+
+fn deserialize_rows<'p>(py: Python<'p>, rows: Rows) -> PyResult<&'p PyList> {
     let columns = rows.columns();
-    let mut rows = rows.iter().map(|row| {
-        let values = (0..columns.len()).map(|col_idx| {
-            let col_ty = columns[col_idx].type_();
-            deserialize_column(py, &row, col_ty, col_idx)
-        });
-        PyTuple::new(py, values)
-    });
-    PyList::new(py, &mut rows)
+    let mut rows = rows
+        .iter()
+        .map(|row| {
+            let values = (0..columns.len())
+                .map(|col_idx| {
+                    let col_ty = columns[col_idx].type_();
+                    deserialize_column(py, &row, col_ty, col_idx)
+                })
+                .collect::<PyResult<Vec<_>>>()?;
+            Ok(PyTuple::new(py, values))
+        })
+        .collect::<PyResult<Vec<_>>>()?;
+    Ok(PyList::new(py, &mut rows))
 }
 
-fn deserialize_column(py: Python<'_>, row: &Row, col_ty: &Type, col_idx: usize) -> PyObject {
-    match *col_ty {
+fn deserialize_column(py: Python<'_>, row: &Row, col_ty: &Type, col_idx: usize) -> PyResult<PyObject> {
+    let value = match *col_ty {
         types::BOOL => row.get::<_, bool>(col_idx).to_object(py),
         types::CHAR => row.get::<_, i8>(col_idx).to_object(py),
-        
+
         types::INT2 => row.get::<_, i16>(col_idx).to_object(py),
         types::INT4 => row.get::<_, i32>(col_idx).to_object(py),
         types::INT8 => row.get::<_, i64>(col_idx).to_object(py),
@@ -98,9 +242,12 @@ fn deserialize_column(py: Python<'_>, row: &Row, col_ty: &Type, col_idx: usize)
 
         types::JSON | types::JSONB => {
             let value = if col_ty == &types::JSON {
-                row.get_bytes(col_idx).expect("couldn't access bytes")
+                row.get_bytes(col_idx)
+                    .ok_or_else(|| decode_error("couldn't access bytes for json column"))?
             } else {
-                &row.get_bytes(col_idx).expect("couldn't access bytes")[1..]
+                &row
+                    .get_bytes(col_idx)
+                    .ok_or_else(|| decode_error("couldn't access bytes for jsonb column"))?[1..]
             };
 
             let deserializer = r#"
@@ -111,14 +258,79 @@ ret = json.loads(bytes(value))
         }
 
         types::UUID => {
-            let value = row.get_bytes(col_idx).expect("couldn't access bytes");
+            let value = row
+                .get_bytes(col_idx)
+                .ok_or_else(|| decode_error("couldn't access bytes for uuid column"))?;
+            deserialize_uuid(py, value)?
+        }
+
+        types::TIMESTAMP => row.get::<_, chrono::NaiveDateTime>(col_idx).to_object(py),
+        types::TIMESTAMPTZ => row
+            .get::<_, chrono::DateTime<chrono::Utc>>(col_idx)
+            .to_object(py),
+        types::DATE => row.get::<_, chrono::NaiveDate>(col_idx).to_object(py),
+        types::TIME => row.get::<_, chrono::NaiveTime>(col_idx).to_object(py),
+
+        types::NUMERIC => {
+            let value = row.get::<_, rust_decimal::Decimal>(col_idx).to_string();
             let deserializer = r#"
-import uuid
-ret = uuid.UUID(bytes=bytes(value))
+import decimal
+ret = decimal.Decimal(value)
 "#;
-            deserialize_bytes_via_python(py, value, deserializer)
+            deserialize_str_via_python(py, &value, deserializer)
         }
 
-        _ => panic!("unknown type {:?}", col_ty),
-    }
+        types::BYTEA => row.get::<_, Vec<u8>>(col_idx).to_object(py),
+
+        // `inet` holds a host address plus an optional mask, so it round-trips
+        // through `ip_interface` (which keeps the host bits); `cidr` is a
+        // network address with its host bits already guaranteed zero by
+        // Postgres, so `ip_network` is the correct (and lossless) match.
+        types::INET => {
+            let value = row.get::<_, ipnetwork::IpNetwork>(col_idx).to_string();
+            let deserializer = r#"
+import ipaddress
+ret = ipaddress.ip_interface(value)
+"#;
+            deserialize_str_via_python(py, &value, deserializer)
+        }
+
+        types::CIDR => {
+            let value = row.get::<_, ipnetwork::IpNetwork>(col_idx).to_string();
+            let deserializer = r#"
+import ipaddress
+ret = ipaddress.ip_network(value)
+"#;
+            deserialize_str_via_python(py, &value, deserializer)
+        }
+
+        types::MACADDR => {
+            let value = row.get::<_, eui48::MacAddress>(col_idx);
+            value.to_hex_string().to_object(py)
+        }
+
+        types::INT2_ARRAY => row.get::<_, Vec<i16>>(col_idx).to_object(py),
+        types::INT4_ARRAY => row.get::<_, Vec<i32>>(col_idx).to_object(py),
+        types::INT8_ARRAY => row.get::<_, Vec<i64>>(col_idx).to_object(py),
+        types::FLOAT4_ARRAY => row.get::<_, Vec<f32>>(col_idx).to_object(py),
+        types::FLOAT8_ARRAY => row.get::<_, Vec<f64>>(col_idx).to_object(py),
+        types::TEXT_ARRAY | types::VARCHAR_ARRAY => {
+            row.get::<_, Vec<String>>(col_idx).to_object(py)
+        }
+
+        _ => return Err(decode_error(format!("unknown type {:?}", col_ty))),
+    };
+
+    Ok(value)
+}
+
+// Builds a python `uuid.UUID` straight from the wire bytes via pyo3's object
+// protocol (import + getattr + call), instead of `deserialize_bytes_via_python`'s
+// `py.run()`, which recompiles a fresh code object out of source text on every
+// single cell - a cost that dominates on wide result sets.
+fn deserialize_uuid(py: Python<'_>, value: &[u8]) -> PyResult<PyObject> {
+    let uuid_cls = py.import("uuid")?.getattr("UUID")?;
+    let kwargs = PyDict::new(py);
+    kwargs.set_item("bytes", value)?;
+    Ok(uuid_cls.call((), Some(kwargs))?.to_object(py))
 }
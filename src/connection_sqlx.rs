@@ -2,11 +2,20 @@ use sqlx::postgres::PgRow;
 use sqlx::row::Row;
 use sqlx::PgPool;
 use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
 use pyo3::prelude::*;
-use pyo3::types::{PyList, PyTuple};
+use pyo3::types::{PyDict, PyList, PyTuple};
 
-use crate::{deserialize_bytes_via_python, executor};
+use crate::errors::{
+    connection_error, decode_error, error_outcome, query_error, resolve_outcome,
+    resolve_outcome_with, success_outcome,
+};
+use crate::options::{is_transient, ConnectionOptions};
+use crate::params::{extract_params, SlonikParam};
+use crate::tls::TlsOptions;
+use crate::{deserialize_bytes_via_python, deserialize_str_via_python, executor};
 
 #[cfg(feature = "cheating")]
 use std::collections::HashMap;
@@ -24,11 +33,17 @@ pub struct SqlxConnection {
 /// An asynchronous PG driver using `sqlx` with the "slonik-rt" async runtime bridge
 /// to python's `asyncio`. 
 /// Contains a terrible infinitely growing cache to see how caching affects benchmarks.
+/// Keyed on `(query, params)` rather than `query` alone, so two calls with
+/// the same query text but different params don't collide on one cache entry.
+/// `params` is folded into the key via its `Debug` output rather than giving
+/// `SlonikParam` real `Hash`/`Eq` impls (which the non-cheating code has no
+/// use for), since this cache only exists to benchmark caching, not to be
+/// rigorous about it.
 #[cfg(feature = "cheating")]
 #[pyclass]
 pub struct SqlxConnection {
     pool: *const PgPool,
-    cache: Arc<Mutex<HashMap<&'static str, PyObject>>>,
+    cache: Arc<Mutex<HashMap<(&'static str, String), PyObject>>>,
 }
 
 impl Drop for SqlxConnection {
@@ -42,48 +57,63 @@ impl Drop for SqlxConnection {
 #[pymethods]
 impl SqlxConnection {
     #[new]
-    fn new(url: &str) -> Self {
+    fn new(
+        url: &str,
+        options: Option<ConnectionOptions>,
+        tls: Option<TlsOptions>,
+    ) -> PyResult<Self> {
         // Note: connecting to PG is an async operation in sql, but here we'll
         // do the same thing as for TcpStreams, block while connecting, but handle
         // queries asynchronously.
-        use futures::executor;
-
-        let builder = PgPool::builder().max_size(1); /* defaut max_size: 10 */
-        let pool = builder.build(url);
-        let pool = executor::block_on(pool).expect("Building pg connection pool failed");
+        let options = options.unwrap_or_default();
+        let tls = tls.unwrap_or_default();
+        let pool = connect_with_backoff(url, &options, &tls)?;
         let pool = Arc::into_raw(Arc::new(pool));
 
         #[cfg(not(feature = "cheating"))]
         {
-            SqlxConnection { pool }
+            Ok(SqlxConnection { pool })
         }
 
         #[cfg(feature = "cheating")]
         {
             let cache = Arc::new(Mutex::new(HashMap::default()));
-            SqlxConnection { pool, cache }
+            Ok(SqlxConnection { pool, cache })
         }
     }
 
-    /// Spawns an async task to execute the given SQL query, bridged to python via 
-    /// the completion callback and IO interest registration.
-    /// As `sqlx` doesn't yet provide a way to access the query's result column types,
-    /// the client has to provide them via the `columns` vec.
+    /// Spawns an async task to execute the given SQL query, bridged to python via
+    /// the completion callback and IO interest registration. `on_done_callback`
+    /// is invoked with a `(ok, value)` pair: `(True, rows)` on success, or
+    /// `(False, exception)` on failure, so python can re-raise and handle
+    /// database failures via normal `try/except`.
+    /// `columns` is only needed to override the types introspected from the
+    /// result rows themselves (e.g. when the server reports a column as
+    /// `unknown`); omit it (or pass `None`) to rely on introspection
+    /// entirely.
+    #[args(columns = "None")]
     fn query(
         &mut self,
         query: &'static str,
-        columns: Vec<String>,
+        columns: Option<Vec<String>>,
+        params: &PyList,
         on_done_callback: PyObject,
         read_registrar: PyObject,
         write_registrar: PyObject,
-    ) {
+    ) -> PyResult<()> {
+        let params = extract_params(params)?;
+
         {
             #[cfg(feature = "cheating")]
             {
+                let cache_key = (query, format!("{:?}", params));
                 let cache = self.cache.lock().unwrap();
-                if let Some(rows) = cache.get(query) {
-                    slonik_rt::execute_python_callback(&on_done_callback, rows);
-                    return;
+                if let Some(rows) = cache.get(&cache_key) {
+                    let gil = Python::acquire_gil();
+                    let py = gil.python();
+                    let outcome = success_outcome(py, rows);
+                    slonik_rt::execute_python_callback(&on_done_callback, outcome);
+                    return Ok(());
                 }
             }
         }
@@ -97,7 +127,9 @@ impl SqlxConnection {
 
         #[cfg(not(feature = "cheating"))]
         {
-            let fut = do_query(pool, query, columns);
+            let fut = resolve_outcome_with(do_query(pool, query, columns, params), |py, rows| {
+                rows.into_pyobject(py)
+            });
             executor::spawn_for_python(
                 fut,
                 on_done_callback,
@@ -109,22 +141,33 @@ impl SqlxConnection {
         #[cfg(feature = "cheating")]
         {
             let cache = Arc::clone(&self.cache);
+            let cache_key = (query, format!("{:?}", params));
 
             let fut = async move {
-                let rows = do_query(pool, query, columns).await;
+                let rows = match do_query(pool, query, columns, params).await {
+                    Ok(rows) => rows,
+                    Err(err) => {
+                        let gil = Python::acquire_gil();
+                        let py = gil.python();
+                        return error_outcome(py, err);
+                    }
+                };
 
                 let gil = Python::acquire_gil();
                 let py = gil.python();
 
-                let rows = rows.to_object(py);
-                let clone = rows.clone_ref(py);
+                let value = match rows.into_pyobject(py) {
+                    Ok(value) => value,
+                    Err(err) => return error_outcome(py, err),
+                };
+                let clone = value.clone_ref(py);
 
                 {
                     let mut cache = cache.lock().unwrap();
-                    cache.insert(query, rows);
+                    cache.insert(cache_key, value);
                 }
 
-                clone
+                success_outcome(py, clone)
             };
 
             executor::spawn_for_python(
@@ -133,7 +176,175 @@ impl SqlxConnection {
                 read_registrar,
                 write_registrar,
             );
-        }        
+        }
+
+        Ok(())
+    }
+
+    /// Like `query`, but streams rows off the wire instead of buffering the
+    /// whole result set: `on_batch_callback` is invoked once per batch of up
+    /// to `batch_size` rows as they arrive, and `on_done_callback` fires
+    /// once with a `(ok, value)` pair once the stream is exhausted —
+    /// `(True, total_row_count)` on success, or `(False, exception)` on
+    /// failure. `columns` can likewise be omitted to rely on introspection.
+    /// `batch_size` must be greater than 0, or the whole result set would
+    /// silently be buffered in memory instead of actually streaming.
+    #[args(columns = "None")]
+    fn query_stream(
+        &mut self,
+        query: &'static str,
+        columns: Option<Vec<String>>,
+        batch_size: usize,
+        params: &PyList,
+        on_batch_callback: PyObject,
+        on_done_callback: PyObject,
+        read_registrar: PyObject,
+        write_registrar: PyObject,
+    ) -> PyResult<()> {
+        if batch_size == 0 {
+            return Err(query_error("batch_size must be greater than 0".to_string(), None));
+        }
+
+        let params = extract_params(params)?;
+
+        let pool = {
+            let pool = unsafe { Arc::from_raw(self.pool) };
+            let clone = Arc::clone(&pool);
+            std::mem::forget(pool);
+            clone
+        };
+
+        let fut = resolve_outcome(do_query_streaming(
+            pool,
+            query,
+            columns,
+            batch_size,
+            params,
+            on_batch_callback,
+        ));
+        executor::spawn_for_python(fut, on_done_callback, read_registrar, write_registrar);
+        Ok(())
+    }
+}
+
+// Builds the pool with exponential backoff: starts at
+// `options.initial_delay_ms`, multiplies the delay by `options.backoff_factor`
+// (2.0, i.e. doubling, by default) up to `options.max_interval_ms` between
+// attempts, bounding each individual
+// attempt to `options.connect_timeout_ms`, and only retries on a transient
+// (connection refused/reset/aborted, or timed out) error; anything else is
+// fatal immediately.
+fn connect_with_backoff(
+    url: &str,
+    options: &ConnectionOptions,
+    tls: &TlsOptions,
+) -> PyResult<PgPool> {
+    let mut delay = Duration::from_millis(options.initial_delay_ms);
+    let max_interval = Duration::from_millis(options.max_interval_ms);
+    let connect_timeout = Duration::from_millis(options.connect_timeout_ms);
+    let mut last_err = None;
+
+    for attempt in 0..=options.max_retries {
+        match connect_once(url, options.max_size, tls, connect_timeout) {
+            Ok(pool) => return Ok(pool),
+            Err(err) => {
+                if attempt == options.max_retries || !is_transient_connect_outcome(&err) {
+                    return Err(connect_outcome_to_pyerr(err));
+                }
+                last_err = Some(err);
+            }
+        }
+
+        thread::sleep(delay);
+        delay = std::cmp::min(delay.mul_f64(options.backoff_factor), max_interval);
+    }
+
+    Err(connect_outcome_to_pyerr(
+        last_err.expect("loop always records an error before exhausting max_retries"),
+    ))
+}
+
+// `PgPool::builder().build()` has no built-in timeout, so the attempt (and
+// the little blocking executor driving it) is run on its own thread and
+// raced against `timeout` via a channel; a connect that's still hanging
+// once `timeout` elapses is abandoned (the thread is left to finish or fail
+// on its own) rather than blocking the retry loop forever.
+//
+// TLS is wired into the pool builder's own connect options (mirroring the
+// real `native-tls` connector `build_tls_mode` builds for the rust-postgres
+// driver) instead of being folded into the connection string: `ssl_mode`
+// maps directly onto our `disable`/`prefer`/`require`, `ssl_root_cert` is
+// only set when a CA cert path was given, and `accept_invalid_certs` is
+// forwarded as its own setting rather than used to silently upgrade `prefer`
+// into `require`.
+fn connect_once(
+    url: &str,
+    max_size: u32,
+    tls: &TlsOptions,
+    timeout: Duration,
+) -> Result<PgPool, ConnectOutcome> {
+    use futures::executor;
+
+    let url = url.to_string();
+    let tls = tls.clone();
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    thread::spawn(move || {
+        let mut builder = PgPool::builder()
+            .max_size(max_size)
+            .ssl_mode(crate::tls::to_sqlx_ssl_mode(&tls))
+            .ssl_accept_invalid_certs(tls.accept_invalid_certs);
+
+        if let Some(ca_cert_path) = &tls.ca_cert_path {
+            builder = builder.ssl_root_cert(ca_cert_path);
+        }
+
+        let result = executor::block_on(builder.build(&url)).map_err(ConnectOutcome::Sqlx);
+        let _ = tx.send(result);
+    });
+
+    rx.recv_timeout(timeout)
+        .unwrap_or(Err(ConnectOutcome::TimedOut(timeout)))
+}
+
+// The two ways `connect_once` can fail, kept distinct so the backoff loop
+// can decide whether each is worth retrying and so the final error message
+// stays accurate.
+enum ConnectOutcome {
+    Sqlx(sqlx::Error),
+    TimedOut(Duration),
+}
+
+fn is_transient_connect_outcome(err: &ConnectOutcome) -> bool {
+    match err {
+        ConnectOutcome::Sqlx(err) => is_transient(err),
+        ConnectOutcome::TimedOut(_) => true,
+    }
+}
+
+fn connect_outcome_to_pyerr(err: ConnectOutcome) -> PyErr {
+    match err {
+        ConnectOutcome::Sqlx(err) => sqlx_connection_error(err),
+        ConnectOutcome::TimedOut(timeout) => {
+            connection_error(format!("connection attempt timed out after {:?}", timeout), None)
+        }
+    }
+}
+
+fn sqlx_connection_error(err: sqlx::Error) -> PyErr {
+    let sqlstate = sqlx_sqlstate(&err);
+    connection_error(err.to_string(), sqlstate.as_deref())
+}
+
+fn sqlx_query_error(err: sqlx::Error) -> PyErr {
+    let sqlstate = sqlx_sqlstate(&err);
+    query_error(err.to_string(), sqlstate.as_deref())
+}
+
+fn sqlx_sqlstate(err: &sqlx::Error) -> Option<String> {
+    match err.as_database_error() {
+        Some(db_err) => db_err.code().map(|code| code.into_owned()),
+        None => None,
     }
 }
 
@@ -144,37 +355,176 @@ struct SlonikRows {
     rows: Vec<PgRow>,
 }
 
-impl pyo3::ToPyObject for SlonikRows {
-    fn to_object(&self, py: Python) -> PyObject {
-        let mut rows = self.rows.iter().map(|row| {
-            let values = self
-                .columns
-                .iter()
-                .enumerate()
-                .map(|(col_idx, col_ty)| deserialize_column(py, &row, col_ty, col_idx));
-            PyTuple::new(py, values)
-        });
-
-        PyList::new(py, &mut rows).to_object(py)
+impl SlonikRows {
+    // A decode failure anywhere aborts the whole conversion with a real
+    // `PyResult::Err` instead of silently swallowing it into a `None`
+    // placeholder cell and reporting success, the way `RustPgConnection`'s
+    // `deserialize_rows` already does via `.collect::<PyResult<Vec<_>>>()?`.
+    fn into_pyobject(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let mut rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let values = self
+                    .columns
+                    .iter()
+                    .enumerate()
+                    .map(|(col_idx, col_ty)| deserialize_column(py, row, col_ty, col_idx))
+                    .collect::<PyResult<Vec<_>>>()?;
+                Ok(PyTuple::new(py, values))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        Ok(PyList::new(py, &mut rows).to_object(py))
     }
 }
 
 // Note: this fetches all results of a query eagerly
-async fn do_query(pool: Arc<PgPool>, query: &'static str, columns: Vec<String>) -> SlonikRows {
+async fn do_query(
+    pool: Arc<PgPool>,
+    query: &'static str,
+    columns: Option<Vec<String>>,
+    params: Vec<SlonikParam>,
+) -> PyResult<SlonikRows> {
     // println!("> starting query: {}", query);
 
     let mut conn = pool.as_ref();
-    let rows = sqlx::query(query).fetch_all(&mut conn);
-    let rows = rows.await.unwrap();
+    let rows = bind_params(sqlx::query(query), &params).fetch_all(&mut conn);
+    let rows = rows.await.map_err(sqlx_query_error)?;
 
+    let columns = columns.unwrap_or_else(|| infer_columns(&rows));
     let result = SlonikRows { columns, rows };
 
     // println!("> query done: {}", query);
-    result
+    Ok(result)
+}
+
+// Introspects column type names straight from the first row's metadata,
+// the way `RustPgConnection` already does via `columns[i].type_()` -
+// avoiding the need for callers to pass them in explicitly. An explicit
+// `columns` override still wins, for cases like the server reporting
+// `unknown` where introspection can't help.
+fn infer_columns(rows: &[PgRow]) -> Vec<String> {
+    match rows.first() {
+        Some(row) => infer_columns_from_row(row),
+        None => Vec::new(),
+    }
 }
 
-fn deserialize_column(py: Python<'_>, row: &PgRow, col_ty: &str, col_idx: usize) -> PyObject {
-    match col_ty {
+fn infer_columns_from_row(row: &PgRow) -> Vec<String> {
+    row.columns()
+        .iter()
+        .map(|column| column.type_info().to_string())
+        .collect()
+}
+
+// Binds the already-extracted parameters positionally, the way sqlx's
+// `.bind(index)` does.
+fn bind_params<'q>(
+    mut query: sqlx::Query<'q, sqlx::Postgres>,
+    params: &[SlonikParam],
+) -> sqlx::Query<'q, sqlx::Postgres> {
+    for param in params {
+        query = match param {
+            SlonikParam::Null => query.bind(SqlxNull),
+            SlonikParam::Bool(value) => query.bind(*value),
+            SlonikParam::I64(value) => query.bind(*value),
+            SlonikParam::F64(value) => query.bind(*value),
+            SlonikParam::Str(value) => query.bind(value.clone()),
+            SlonikParam::Bytes(value) => query.bind(value.clone()),
+        };
+    }
+    query
+}
+
+/// A parameter that always serializes as SQL `NULL` with the Postgres
+/// "unspecified" type OID, the same untyped-null trick `RustPgConnection`'s
+/// `SqlNull` uses via `ToSql::accepts`, so the server infers the column's
+/// actual type instead of the NULL being pinned to `int8` regardless of
+/// what it's bound against.
+struct SqlxNull;
+
+impl sqlx::types::Type<sqlx::Postgres> for SqlxNull {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        sqlx::postgres::PgTypeInfo::with_oid(0)
+    }
+}
+
+impl sqlx::encode::Encode<sqlx::Postgres> for SqlxNull {
+    fn encode(&self, _buf: &mut Vec<u8>) -> sqlx::encode::IsNull {
+        sqlx::encode::IsNull::Yes
+    }
+}
+
+// Pulls rows off the wire incrementally via sqlx's `.fetch()` stream instead
+// of materializing the whole result set, invoking `on_batch_callback` once
+// per accumulated batch of up to `batch_size` rows. Backpressure is
+// implicit: the stream only advances as fast as we drain and dispatch each
+// batch.
+async fn do_query_streaming(
+    pool: Arc<PgPool>,
+    query: &'static str,
+    columns: Option<Vec<String>>,
+    batch_size: usize,
+    params: Vec<SlonikParam>,
+    on_batch_callback: PyObject,
+) -> PyResult<usize> {
+    use futures::StreamExt;
+
+    let mut conn = pool.as_ref();
+    let mut stream = bind_params(sqlx::query(query), &params).fetch(&mut conn);
+
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut columns = columns;
+    let mut total = 0;
+
+    while let Some(row) = stream.next().await {
+        let row = row.map_err(sqlx_query_error)?;
+        if columns.is_none() {
+            columns = Some(infer_columns_from_row(&row));
+        }
+        batch.push(row);
+
+        if batch.len() == batch_size {
+            total += dispatch_batch(columns.as_ref().unwrap(), &mut batch, batch_size, &on_batch_callback)?;
+        }
+    }
+
+    if !batch.is_empty() {
+        let columns = columns
+            .as_ref()
+            .expect("columns are inferred once the first row arrives");
+        total += dispatch_batch(columns, &mut batch, batch_size, &on_batch_callback)?;
+    }
+
+    Ok(total)
+}
+
+// Hands one accumulated batch to python and returns how many rows it held.
+// A decode failure anywhere in the batch aborts the whole stream with a
+// real error instead of silently handing python a partially-decoded batch.
+fn dispatch_batch(
+    columns: &[String],
+    batch: &mut Vec<PgRow>,
+    batch_size: usize,
+    on_batch_callback: &PyObject,
+) -> PyResult<usize> {
+    let rows = SlonikRows {
+        columns: columns.to_vec(),
+        rows: std::mem::replace(batch, Vec::with_capacity(batch_size)),
+    };
+    let len = rows.rows.len();
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let rows = rows.into_pyobject(py)?;
+    slonik_rt::execute_python_callback(on_batch_callback, rows);
+
+    Ok(len)
+}
+
+fn deserialize_column(py: Python<'_>, row: &PgRow, col_ty: &str, col_idx: usize) -> PyResult<PyObject> {
+    let value = match col_ty {
         "bool" | "BOOL" => row.get::<bool, _>(col_idx).to_object(py),
 
         "int2" | "INT2" => row.get::<i16, _>(col_idx).to_object(py),
@@ -208,13 +558,80 @@ ret = json.loads(bytes(value))
 
         "uuid" | "UUID" => {
             let value = row.get::<Vec<u8>, _>(col_idx);
+            deserialize_uuid(py, &value)?
+        }
+
+        // Decoded from sqlx's typed accessors directly, avoiding the
+        // per-cell python `eval` round trip the json case above still needs.
+        "timestamp" | "TIMESTAMP" => row
+            .get::<chrono::NaiveDateTime, _>(col_idx)
+            .to_object(py),
+        "timestamptz" | "TIMESTAMPTZ" => row
+            .get::<chrono::DateTime<chrono::Utc>, _>(col_idx)
+            .to_object(py),
+        "date" | "DATE" => row.get::<chrono::NaiveDate, _>(col_idx).to_object(py),
+        "time" | "TIME" => row.get::<chrono::NaiveTime, _>(col_idx).to_object(py),
+
+        "numeric" | "decimal" | "NUMERIC" | "DECIMAL" => {
+            let value = row.get::<rust_decimal::Decimal, _>(col_idx).to_string();
             const DESERIALIZER: &str = r#"
-import uuid
-ret = uuid.UUID(bytes=bytes(value))
+import decimal
+ret = decimal.Decimal(value)
 "#;
-            deserialize_bytes_via_python(py, &value, DESERIALIZER)
+            deserialize_str_via_python(py, &value, DESERIALIZER)
         }
 
-        _ => panic!("unknown type {:?}", col_ty),
-    }
+        "bytea" | "BYTEA" => row.get::<Vec<u8>, _>(col_idx).to_object(py),
+
+        // `inet` holds a host address plus an optional mask, so it round-trips
+        // through `ip_interface` (which keeps the host bits); `cidr` is a
+        // network address with its host bits already guaranteed zero by
+        // Postgres, so `ip_network` is the correct (and lossless) match.
+        "inet" | "INET" => {
+            let value = row.get::<ipnetwork::IpNetwork, _>(col_idx).to_string();
+            const DESERIALIZER: &str = r#"
+import ipaddress
+ret = ipaddress.ip_interface(value)
+"#;
+            deserialize_str_via_python(py, &value, DESERIALIZER)
+        }
+
+        "cidr" | "CIDR" => {
+            let value = row.get::<ipnetwork::IpNetwork, _>(col_idx).to_string();
+            const DESERIALIZER: &str = r#"
+import ipaddress
+ret = ipaddress.ip_network(value)
+"#;
+            deserialize_str_via_python(py, &value, DESERIALIZER)
+        }
+
+        "macaddr" | "MACADDR" => row
+            .get::<eui48::MacAddress, _>(col_idx)
+            .to_hex_string()
+            .to_object(py),
+
+        "int2[]" | "INT2[]" => row.get::<Vec<i16>, _>(col_idx).to_object(py),
+        "int4[]" | "INT4[]" => row.get::<Vec<i32>, _>(col_idx).to_object(py),
+        "int8[]" | "INT8[]" => row.get::<Vec<i64>, _>(col_idx).to_object(py),
+        "float4[]" | "FLOAT4[]" => row.get::<Vec<f32>, _>(col_idx).to_object(py),
+        "float8[]" | "FLOAT8[]" => row.get::<Vec<f64>, _>(col_idx).to_object(py),
+        "text[]" | "varchar[]" | "TEXT[]" | "VARCHAR[]" => {
+            row.get::<Vec<String>, _>(col_idx).to_object(py)
+        }
+
+        _ => return Err(decode_error(format!("unknown type {:?}", col_ty))),
+    };
+
+    Ok(value)
+}
+
+// Builds a python `uuid.UUID` straight from the wire bytes via pyo3's object
+// protocol (import + getattr + call), instead of `deserialize_bytes_via_python`'s
+// `py.run()`, which recompiles a fresh code object out of source text on every
+// single cell - a cost that dominates on wide result sets.
+fn deserialize_uuid(py: Python<'_>, value: &[u8]) -> PyResult<PyObject> {
+    let uuid_cls = py.import("uuid")?.getattr("UUID")?;
+    let kwargs = PyDict::new(py);
+    kwargs.set_item("bytes", value)?;
+    Ok(uuid_cls.call((), Some(kwargs))?.to_object(py))
 }
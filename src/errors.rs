@@ -0,0 +1,86 @@
+use std::future::Future;
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::PyErr;
+
+/// Base of the slonik exception hierarchy, raised instead of panicking
+/// across the FFI boundary whenever a database or decoding operation fails.
+create_exception!(slonik_proto, SlonikError, PyException);
+
+/// Raised when establishing or re-establishing a connection fails.
+create_exception!(slonik_proto, ConnectionError, SlonikError);
+
+/// Raised when executing a query (or preparing/streaming one) fails.
+create_exception!(slonik_proto, QueryError, SlonikError);
+
+/// Raised when a returned column can't be decoded to a Python value.
+create_exception!(slonik_proto, DecodeError, SlonikError);
+
+/// Builds a `ConnectionError` carrying the PG SQLSTATE code when available.
+pub(crate) fn connection_error(message: String, sqlstate: Option<&str>) -> PyErr {
+    PyErr::new::<ConnectionError, _>(with_sqlstate(message, sqlstate))
+}
+
+/// Builds a `QueryError` carrying the PG SQLSTATE code when available.
+pub(crate) fn query_error(message: String, sqlstate: Option<&str>) -> PyErr {
+    PyErr::new::<QueryError, _>(with_sqlstate(message, sqlstate))
+}
+
+/// Builds a `DecodeError` for a column that couldn't be turned into a
+/// Python value (e.g. an unrecognized PG type).
+pub(crate) fn decode_error(message: impl Into<String>) -> PyErr {
+    PyErr::new::<DecodeError, _>(message.into())
+}
+
+fn with_sqlstate(message: String, sqlstate: Option<&str>) -> String {
+    match sqlstate {
+        Some(code) => format!("{} (SQLSTATE {})", message, code),
+        None => message,
+    }
+}
+
+// Bridges a fallible future to `spawn_for_python`, which expects an
+// infallible `ToPyObject` output. `PyErr::restore` only raises once a
+// pyo3-wrapped function returns `Err` up through the call stack to the
+// interpreter; `on_done_callback` is invoked directly instead, so restoring
+// the error here and handing back `None` would just make the callback
+// observe a fabricated success. Hand back a `(ok, value)` pair instead, so
+// the python side can do `ok, value = result; ... if not ok: raise value`
+// (or equivalent) and get normal `try/except` semantics around the call.
+pub(crate) async fn resolve_outcome<T: ToPyObject>(
+    fut: impl Future<Output = PyResult<T>>,
+) -> PyObject {
+    resolve_outcome_with(fut, |py, value| Ok(value.to_object(py))).await
+}
+
+// Like `resolve_outcome`, but for a `T` whose conversion to a `PyObject` can
+// itself fail (e.g. a row holding an unrecognized column type) - that
+// conversion error is reported the same as any other failure of `fut`,
+// rather than producing a fabricated, partially-valid success value.
+pub(crate) async fn resolve_outcome_with<T>(
+    fut: impl Future<Output = PyResult<T>>,
+    into_pyobject: impl FnOnce(Python<'_>, T) -> PyResult<PyObject>,
+) -> PyObject {
+    let result = fut.await;
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    match result.and_then(|value| into_pyobject(py, value)) {
+        Ok(value) => success_outcome(py, value),
+        Err(err) => error_outcome(py, err),
+    }
+}
+
+/// Wraps a successful result as the `(True, value)` pair `on_done_callback`
+/// receives.
+pub(crate) fn success_outcome(py: Python<'_>, value: impl ToPyObject) -> PyObject {
+    (true, value.to_object(py)).to_object(py)
+}
+
+/// Wraps a failure as the `(False, exception)` pair `on_done_callback`
+/// receives, carrying the actual exception instance rather than a
+/// fabricated placeholder value.
+pub(crate) fn error_outcome(py: Python<'_>, err: PyErr) -> PyObject {
+    (false, err.instance(py).to_object(py)).to_object(py)
+}
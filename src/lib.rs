@@ -2,6 +2,11 @@
 
 mod connection_rust_pg;
 mod connection_sqlx;
+mod errors;
+mod options;
+mod params;
+mod pubsub;
+mod tls;
 
 use pyo3::prelude::*;
 use pyo3::types::PyDict;
@@ -45,6 +50,15 @@ fn slonik_proto(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     // - and one for the `sqlx` crate
     m.add_class::<connection_rust_pg::RustPgConnection>()?;
     m.add_class::<connection_sqlx::SqlxConnection>()?;
+    m.add_class::<pubsub::PubSubConnection>()?;
+    m.add_class::<options::ConnectionOptions>()?;
+    m.add_class::<tls::TlsOptions>()?;
+
+    // Exception hierarchy raised by the drivers instead of panicking.
+    m.add("SlonikError", _py.get_type::<errors::SlonikError>())?;
+    m.add("ConnectionError", _py.get_type::<errors::ConnectionError>())?;
+    m.add("QueryError", _py.get_type::<errors::QueryError>())?;
+    m.add("DecodeError", _py.get_type::<errors::DecodeError>())?;
 
     // asyncio registrar interface
     m.add_wrapped(wrap_pyfunction!(on_fd_read_ready))?;
@@ -96,6 +110,25 @@ pub(crate) fn deserialize_bytes_via_python(
     ret.to_object(py)
 }
 
+// Same as `deserialize_bytes_via_python`, but for types (decimals, network
+// addresses, ...) that are more naturally handed to python as their string
+// representation than as raw bytes.
+pub(crate) fn deserialize_str_via_python(
+    py: Python<'_>,
+    value: &str,
+    python_deserializer: &str,
+) -> PyObject {
+    let locals = PyDict::new(py);
+    locals
+        .set_item("value", value)
+        .expect("setting local str `value` failed");
+
+    py.run(python_deserializer, None, Some(locals))
+        .expect("eval error");
+    let ret = locals.get_item("ret").expect("error getting `ret` local");
+    ret.to_object(py)
+}
+
 // --- The following are the different examples -----
 
 /// Example connecting to pg via sqlx and executing the query, which is expected to return
@@ -0,0 +1,84 @@
+use pyo3::prelude::*;
+
+/// Connection tuning shared by both drivers: pool size, how long a single
+/// connect attempt is allowed to take, and the exponential-backoff policy
+/// used while (re-)establishing the initial connection.
+#[pyclass]
+#[derive(Clone)]
+pub struct ConnectionOptions {
+    #[pyo3(get, set)]
+    pub max_size: u32,
+    #[pyo3(get, set)]
+    pub connect_timeout_ms: u64,
+    #[pyo3(get, set)]
+    pub initial_delay_ms: u64,
+    #[pyo3(get, set)]
+    pub max_interval_ms: u64,
+    #[pyo3(get, set)]
+    pub backoff_factor: f64,
+    #[pyo3(get, set)]
+    pub max_retries: u32,
+}
+
+#[pymethods]
+impl ConnectionOptions {
+    #[new]
+    #[args(
+        max_size = 10,
+        connect_timeout_ms = 5_000,
+        initial_delay_ms = 100,
+        max_interval_ms = 30_000,
+        backoff_factor = 2.0,
+        max_retries = 5
+    )]
+    fn new(
+        max_size: u32,
+        connect_timeout_ms: u64,
+        initial_delay_ms: u64,
+        max_interval_ms: u64,
+        backoff_factor: f64,
+        max_retries: u32,
+    ) -> Self {
+        ConnectionOptions {
+            max_size,
+            connect_timeout_ms,
+            initial_delay_ms,
+            max_interval_ms,
+            backoff_factor,
+            max_retries,
+        }
+    }
+}
+
+impl Default for ConnectionOptions {
+    fn default() -> Self {
+        ConnectionOptions {
+            max_size: 10,
+            connect_timeout_ms: 5_000,
+            initial_delay_ms: 100,
+            max_interval_ms: 30_000,
+            backoff_factor: 2.0,
+            max_retries: 5,
+        }
+    }
+}
+
+// Walks an error's `source()` chain looking for the underlying `io::Error`,
+// so both drivers can decide whether a connect failure is worth retrying.
+// Only connection-refused/reset/aborted are treated as transient; anything
+// else (auth failures, bad DSNs, ...) is returned to the caller immediately.
+pub(crate) fn is_transient(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(err);
+    while let Some(err) = source {
+        if let Some(io_err) = err.downcast_ref::<std::io::Error>() {
+            return matches!(
+                io_err.kind(),
+                std::io::ErrorKind::ConnectionRefused
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+            );
+        }
+        source = err.source();
+    }
+    false
+}
@@ -0,0 +1,47 @@
+use pyo3::prelude::*;
+use pyo3::types::PyList;
+use pyo3::PyResult;
+
+use crate::errors::query_error;
+
+/// A query parameter extracted from a Python value, ready to be bound
+/// positionally by either driver.
+#[derive(Debug, Clone)]
+pub(crate) enum SlonikParam {
+    Null,
+    Bool(bool),
+    I64(i64),
+    F64(f64),
+    Str(String),
+    Bytes(Vec<u8>),
+}
+
+/// Extracts the positional parameters out of the Python list passed to
+/// `query`, in the order they should be bound. Fails with a `QueryError`
+/// if a parameter isn't one of the supported types, rather than panicking
+/// on attacker-reachable input.
+pub(crate) fn extract_params(params: &PyList) -> PyResult<Vec<SlonikParam>> {
+    params.iter().map(extract_param).collect()
+}
+
+fn extract_param(value: &PyAny) -> PyResult<SlonikParam> {
+    // `bool` must be checked before `i64`, since python bools are ints too.
+    if value.is_none() {
+        Ok(SlonikParam::Null)
+    } else if let Ok(value) = value.extract::<bool>() {
+        Ok(SlonikParam::Bool(value))
+    } else if let Ok(value) = value.extract::<i64>() {
+        Ok(SlonikParam::I64(value))
+    } else if let Ok(value) = value.extract::<f64>() {
+        Ok(SlonikParam::F64(value))
+    } else if let Ok(value) = value.extract::<Vec<u8>>() {
+        Ok(SlonikParam::Bytes(value))
+    } else if let Ok(value) = value.extract::<String>() {
+        Ok(SlonikParam::Str(value))
+    } else {
+        Err(query_error(
+            format!("unsupported parameter type: {}", value.get_type().name()),
+            None,
+        ))
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use postgres::{Connection, TlsMode};
+use pyo3::prelude::*;
+
+use crate::errors::{connection_error, error_outcome, query_error, success_outcome};
+
+/// A dedicated PG connection used to subscribe to `LISTEN`/`NOTIFY` channels
+/// and bridge incoming notifications to python's `asyncio`.
+///
+/// Driven by the blocking `postgres` crate on its own OS thread, the same
+/// way `RustPgConnection` talks to PG, rather than `tokio_postgres`: this
+/// crate never starts a Tokio runtime/reactor (everything else is bridged
+/// to asyncio by hand via `slonik_rt`'s executor/reactor), and polling a
+/// `tokio_postgres` future without one panics at the first socket
+/// operation.
+#[pyclass]
+pub struct PubSubConnection {
+    url: String,
+    // One stop flag per channel currently being listened on, so `unlisten`
+    // can tell the matching `listen` thread to issue `UNLISTEN` and return,
+    // and so a second `listen` on the same channel can be rejected instead
+    // of orphaning the first thread.
+    listeners: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+}
+
+#[pymethods]
+impl PubSubConnection {
+    #[new]
+    fn new(url: &str) -> Self {
+        PubSubConnection {
+            url: url.to_string(),
+            listeners: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Opens a dedicated connection, issues `LISTEN <channel>`, and invokes
+    /// `on_notification_callback` with each `(channel, payload)` pair as
+    /// `NOTIFY`s arrive. Runs until `unlisten(channel)` is called or the
+    /// connection is closed or errors, at which point `on_done_callback`
+    /// fires with a `(ok, value)` pair: `(True, None)` on a clean stop, or
+    /// `(False, exception)` on failure. Fails immediately (without spawning
+    /// anything) if `channel` is already being listened on.
+    fn listen(
+        &mut self,
+        channel: String,
+        on_notification_callback: PyObject,
+        on_done_callback: PyObject,
+    ) -> PyResult<()> {
+        validate_channel(&channel)?;
+
+        let mut listeners = self.listeners.lock().unwrap();
+        if listeners.contains_key(&channel) {
+            return Err(query_error(
+                format!("already listening on channel {:?}", channel),
+                None,
+            ));
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        listeners.insert(channel.clone(), Arc::clone(&stop));
+        drop(listeners);
+
+        let url = self.url.clone();
+        let listeners = Arc::clone(&self.listeners);
+
+        thread::spawn(move || {
+            let result = run_listener(&url, &channel, &on_notification_callback, &stop);
+            listeners.lock().unwrap().remove(&channel);
+
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            let outcome = match result {
+                Ok(()) => success_outcome(py, py.None()),
+                Err(err) => error_outcome(py, err),
+            };
+            slonik_rt::execute_python_callback(&on_done_callback, outcome);
+        });
+
+        Ok(())
+    }
+
+    /// Signals the matching `listen(channel)` thread to issue `UNLISTEN
+    /// <channel>` and stop. A no-op if nothing is currently listening on
+    /// that channel.
+    fn unlisten(&mut self, channel: String) -> PyResult<()> {
+        validate_channel(&channel)?;
+
+        if let Some(stop) = self.listeners.lock().unwrap().get(&channel) {
+            stop.store(true, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+}
+
+// Postgres channel identifiers are spliced directly into `LISTEN`/`UNLISTEN`
+// statement text (neither supports parameter binding), so without this check
+// a channel like `"foo; DROP TABLE users;--"` would run arbitrary SQL via
+// `execute`. Restrict to a plain, unquoted SQL identifier.
+fn validate_channel(channel: &str) -> PyResult<()> {
+    let mut chars = channel.chars();
+    let starts_ok = chars
+        .next()
+        .map_or(false, |c| c.is_ascii_alphabetic() || c == '_');
+    let rest_ok = chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    if starts_ok && rest_ok {
+        Ok(())
+    } else {
+        Err(query_error(
+            format!(
+                "invalid LISTEN/NOTIFY channel name {:?}, expected [A-Za-z_][A-Za-z0-9_]*",
+                channel
+            ),
+            None,
+        ))
+    }
+}
+
+// How long each wait for the next notification blocks before re-checking
+// `stop`, so `unlisten` is noticed promptly without busy-looping.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+// Connects, issues `LISTEN`, then blocks on the connection's notification
+// iterator (bounded by `POLL_INTERVAL` so `stop` gets rechecked regularly),
+// dispatching each notification as it arrives. Returns once `stop` is set
+// (issuing `UNLISTEN` first) or the connection is closed or errors.
+fn run_listener(
+    url: &str,
+    channel: &str,
+    on_notification_callback: &PyObject,
+    stop: &AtomicBool,
+) -> PyResult<()> {
+    let connection = Connection::connect(url, TlsMode::None)
+        .map_err(|err| connection_error(err.to_string(), err.code().map(|c| c.code())))?;
+
+    connection
+        .execute(&format!("LISTEN {}", channel), &[])
+        .map_err(|err| connection_error(err.to_string(), err.code().map(|c| c.code())))?;
+
+    let notifications = connection.notifications();
+    let mut iter = notifications.timeout_iter(POLL_INTERVAL);
+
+    while !stop.load(Ordering::SeqCst) {
+        match iter.next() {
+            Some(Ok(notification)) => {
+                dispatch_notification(on_notification_callback, &notification)
+            }
+            // Nothing arrived within `POLL_INTERVAL`; loop back and recheck `stop`.
+            None => {}
+            Some(Err(err)) => return Err(connection_error(err.to_string(), None)),
+        }
+    }
+
+    connection
+        .execute(&format!("UNLISTEN {}", channel), &[])
+        .map_err(|err| connection_error(err.to_string(), err.code().map(|c| c.code())))?;
+
+    Ok(())
+}
+
+fn dispatch_notification(
+    on_notification_callback: &PyObject,
+    notification: &postgres::notification::Notification,
+) {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let payload = (notification.channel.as_str(), notification.payload.as_str()).to_object(py);
+    slonik_rt::execute_python_callback(on_notification_callback, payload);
+}
@@ -0,0 +1,88 @@
+use pyo3::prelude::*;
+
+/// TLS configuration for a connection, mirroring libpq's `sslmode` in
+/// simplified form, plus an optional CA certificate path and whether to
+/// accept otherwise-invalid (e.g. self-signed) server certificates.
+#[pyclass]
+#[derive(Clone)]
+pub struct TlsOptions {
+    #[pyo3(get, set)]
+    pub mode: String,
+    #[pyo3(get, set)]
+    pub ca_cert_path: Option<String>,
+    #[pyo3(get, set)]
+    pub accept_invalid_certs: bool,
+}
+
+#[pymethods]
+impl TlsOptions {
+    #[new]
+    #[args(mode = "\"disable\".to_string()", ca_cert_path = "None", accept_invalid_certs = "false")]
+    fn new(mode: String, ca_cert_path: Option<String>, accept_invalid_certs: bool) -> PyResult<Self> {
+        match mode.as_str() {
+            "disable" | "prefer" | "require" => Ok(TlsOptions {
+                mode,
+                ca_cert_path,
+                accept_invalid_certs,
+            }),
+            other => Err(crate::errors::connection_error(
+                format!("unknown tls mode {:?}, expected disable/prefer/require", other),
+                None,
+            )),
+        }
+    }
+}
+
+impl Default for TlsOptions {
+    fn default() -> Self {
+        TlsOptions {
+            mode: "disable".to_string(),
+            ca_cert_path: None,
+            accept_invalid_certs: false,
+        }
+    }
+}
+
+// Builds the `postgres::TlsMode` rust-postgres expects for `Connection::connect`,
+// wiring `accept_invalid_certs`/`ca_cert_path` into a `native-tls` connector.
+// Handshake/cert-loading failures are surfaced as a `ConnectionError` instead
+// of panicking.
+pub(crate) fn build_tls_mode(tls: &TlsOptions) -> PyResult<postgres::TlsMode> {
+    if tls.mode == "disable" {
+        return Ok(postgres::TlsMode::None);
+    }
+
+    let mut builder = native_tls::TlsConnector::builder();
+    builder.danger_accept_invalid_certs(tls.accept_invalid_certs);
+
+    if let Some(ca_cert_path) = &tls.ca_cert_path {
+        let pem = std::fs::read(ca_cert_path)
+            .map_err(|err| crate::errors::connection_error(err.to_string(), None))?;
+        let cert = native_tls::Certificate::from_pem(&pem)
+            .map_err(|err| crate::errors::connection_error(err.to_string(), None))?;
+        builder.add_root_certificate(cert);
+    }
+
+    let connector = builder
+        .build()
+        .map_err(|err| crate::errors::connection_error(err.to_string(), None))?;
+    let negotiator = Box::new(postgres_native_tls::NativeTls::from(connector));
+
+    Ok(match tls.mode.as_str() {
+        "prefer" => postgres::TlsMode::Prefer(negotiator),
+        _ => postgres::TlsMode::Require(negotiator),
+    })
+}
+
+// Maps our simplified `mode` onto the `PgSslMode` sqlx's own connect options
+// understand, the same `disable`/`prefer`/`require` three-way `build_tls_mode`
+// maps onto `postgres::TlsMode` above - left as a direct 1:1 mapping rather
+// than using `accept_invalid_certs` to upgrade `prefer` into `require`, since
+// that would silently turn off `prefer`'s documented plaintext fallback.
+pub(crate) fn to_sqlx_ssl_mode(tls: &TlsOptions) -> sqlx::postgres::PgSslMode {
+    match tls.mode.as_str() {
+        "prefer" => sqlx::postgres::PgSslMode::Prefer,
+        "require" => sqlx::postgres::PgSslMode::Require,
+        _ => sqlx::postgres::PgSslMode::Disable,
+    }
+}